@@ -1,7 +1,9 @@
+use chrono::{Duration, Utc};
 use clap::{Parser, Subcommand};
-use reqwest;
-use serde::Deserialize;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::path::PathBuf;
 
 /// A simple CLI to fetch cryptocurrency data
 #[derive(Parser)]
@@ -9,6 +11,14 @@ use std::collections::HashMap;
 struct Cli {
     #[command(subcommand)]
     command: Commands,
+
+    /// Quotes provider to try first (e.g. coinpaprika, coingecko). Falls back to the others on failure.
+    #[arg(long, global = true, default_value = "coinpaprika")]
+    source: String,
+
+    /// Maximum age, in seconds, of a cached quote before it is refetched
+    #[arg(long, global = true, default_value_t = 60)]
+    max_age: u64,
 }
 
 #[derive(Subcommand)]
@@ -36,6 +46,17 @@ enum Commands {
         /// Target currency (e.g., usd, usdt)
         target_currency: String,
     },
+    /// Get the daily price history for a coin over the last N days
+    PriceHistory {
+        /// Coin ID (e.g., btc-bitcoin)
+        coin_id: String,
+        /// Target currency (e.g., usd, usdt)
+        target_currency: String,
+        /// Number of days of history to show
+        days: u32,
+    },
+    /// List tradable pairs along with their price/quantity decimal scales
+    ExchangeInfo,
 }
 
 #[derive(Deserialize, Debug)]
@@ -55,7 +76,7 @@ struct CoinDetail {
     rank: u32,
 }
 
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, Serialize, Debug, Clone)]
 struct TickerResponse {
     id: String,
     name: String,
@@ -64,9 +85,191 @@ struct TickerResponse {
     quotes: HashMap<String, MarketQuote>,
 }
 
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, Serialize, Debug, Clone)]
 struct MarketQuote {
-    price: f64,
+    /// Deserialized via `arbitrary_precision` so a non-integer JSON price round-trips exactly
+    /// instead of passing through an f64 first. Requires the `rust_decimal/serde-arbitrary-precision`
+    /// and `serde_json/arbitrary_precision` Cargo features to be enabled.
+    #[serde(with = "rust_decimal::serde::arbitrary_precision")]
+    price: Decimal,
+}
+
+/// One day of coinpaprika's OHLCV data. The `ohlcv/historical` endpoint only ever reports in USD.
+#[derive(Deserialize, Debug)]
+struct HistoricalQuote {
+    #[serde(rename = "time_open")]
+    timestamp: String,
+    #[serde(with = "rust_decimal::serde::arbitrary_precision")]
+    open: Decimal,
+    #[serde(with = "rust_decimal::serde::arbitrary_precision")]
+    high: Decimal,
+    #[serde(with = "rust_decimal::serde::arbitrary_precision")]
+    low: Decimal,
+    #[serde(with = "rust_decimal::serde::arbitrary_precision")]
+    close: Decimal,
+    #[serde(with = "rust_decimal::serde::arbitrary_precision")]
+    volume: Decimal,
+}
+
+/// coinpaprika's free tier only retains historical tickers for the last year
+const FREE_TIER_MAX_HISTORY_DAYS: u32 = 365;
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+struct CachedQuote {
+    fetched_at: i64,
+    response: TickerResponse,
+}
+
+type QuoteCache = HashMap<String, CachedQuote>;
+
+fn quote_cache_path() -> PathBuf {
+    std::env::temp_dir().join("crypto_cli_tool_quote_cache.json")
+}
+
+fn load_quote_cache() -> QuoteCache {
+    std::fs::read_to_string(quote_cache_path())
+        .ok()
+        .and_then(|contents| serde_json::from_str(&contents).ok())
+        .unwrap_or_default()
+}
+
+fn save_quote_cache(cache: &QuoteCache) {
+    if let Ok(json) = serde_json::to_string(cache) {
+        let _ = std::fs::write(quote_cache_path(), json);
+    }
+}
+
+/// A lock file older than this is assumed to belong to a holder that died between `create_new`
+/// and `remove_file` (Ctrl-C, OOM-kill, panic) rather than one genuinely still in the critical
+/// section, and is stolen instead of wedging every future invocation forever.
+const STALE_LOCK_AGE: std::time::Duration = std::time::Duration::from_secs(30);
+
+/// Runs `f` while holding an exclusive advisory lock on the quote cache, so two concurrent CLI
+/// invocations can't read-modify-write the cache file and clobber each other's just-fetched quote.
+/// Uses a sibling lock file created with `create_new`, which is atomic on the filesystems we target.
+fn with_quote_cache_lock<T>(f: impl FnOnce() -> T) -> T {
+    let lock_path = quote_cache_path().with_extension("lock");
+    loop {
+        match std::fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&lock_path)
+        {
+            Ok(_) => break,
+            Err(_) => {
+                let is_stale = std::fs::metadata(&lock_path)
+                    .and_then(|meta| meta.modified())
+                    .and_then(|modified| {
+                        modified
+                            .elapsed()
+                            .map_err(std::io::Error::other)
+                    })
+                    .map(|age| age > STALE_LOCK_AGE)
+                    .unwrap_or(false);
+                if is_stale {
+                    let _ = std::fs::remove_file(&lock_path);
+                }
+                std::thread::sleep(std::time::Duration::from_millis(10));
+            }
+        }
+    }
+
+    let result = f();
+
+    let _ = std::fs::remove_file(&lock_path);
+    result
+}
+
+/// Whether a quote fetched at `fetched_at` (unix seconds) is older than `max_age` seconds.
+/// A `fetched_at` in the future (clock skew) is treated as fresh, not outdated.
+fn is_outdated_quote(fetched_at: i64, max_age: u64) -> bool {
+    let age = Utc::now().timestamp().saturating_sub(fetched_at);
+    age >= 0 && age as u64 > max_age
+}
+
+/// Fetches `coin_id`'s ticker, serving it from the on-disk cache when a fresh-enough entry exists.
+async fn fetch_ticker_cached(coin_id: &str, max_age: u64) -> Result<TickerResponse, Box<dyn std::error::Error>> {
+    if let Some(cached) = load_quote_cache().get(coin_id) {
+        if !is_outdated_quote(cached.fetched_at, max_age) {
+            return Ok(cached.response.clone());
+        }
+    }
+
+    let url = format!("https://api.coinpaprika.com/v1/tickers/{}", coin_id);
+    let response = reqwest::get(&url).await?.json::<TickerResponse>().await?;
+
+    let fetched_at = Utc::now().timestamp();
+    let response_to_cache = response.clone();
+    with_quote_cache_lock(|| {
+        let mut cache = load_quote_cache();
+        cache.insert(
+            coin_id.to_string(),
+            CachedQuote {
+                fetched_at,
+                response: response_to_cache,
+            },
+        );
+        save_quote_cache(&cache);
+    });
+
+    Ok(response)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fresh_quote_within_max_age_is_not_outdated() {
+        assert!(!is_outdated_quote(Utc::now().timestamp(), 60));
+    }
+
+    #[test]
+    fn quote_older_than_max_age_is_outdated() {
+        let fetched_at = Utc::now().timestamp() - 120;
+        assert!(is_outdated_quote(fetched_at, 60));
+    }
+
+    #[test]
+    fn quote_exactly_at_max_age_boundary_is_not_outdated() {
+        let fetched_at = Utc::now().timestamp() - 60;
+        assert!(!is_outdated_quote(fetched_at, 60));
+    }
+
+    #[test]
+    fn fetched_at_in_the_future_from_clock_skew_is_not_outdated() {
+        let fetched_at = Utc::now().timestamp() + 30;
+        assert!(!is_outdated_quote(fetched_at, 60));
+    }
+
+    #[test]
+    fn market_quote_price_round_trips_exactly_at_high_precision() {
+        let json = r#"{"price": 1234.123456789}"#;
+        let quote: MarketQuote = serde_json::from_str(json).unwrap();
+        assert_eq!(quote.price, "1234.123456789".parse::<Decimal>().unwrap());
+    }
+}
+
+#[derive(Deserialize, Debug)]
+struct ExchangeInfo {
+    #[serde(rename = "serverTime")]
+    server_time: i64,
+    symbols: Vec<PairInfo>,
+}
+
+#[derive(Deserialize, Debug)]
+struct PairInfo {
+    symbol: String,
+    #[serde(rename = "baseAsset")]
+    base_asset: String,
+    #[serde(rename = "quoteAsset")]
+    quote_asset: String,
+    /// Number of significant decimal digits supported in the quoted price
+    #[serde(rename = "quoteAssetPrecision")]
+    price_scale: u32,
+    /// Number of significant decimal digits supported in the traded quantity
+    #[serde(rename = "baseAssetPrecision")]
+    quantity_scale: u32,
 }
 
 #[tokio::main]
@@ -85,6 +288,10 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     println!("  - CoinDetails <coin_id>   -> Show details for a specific coin");
     println!("  - CoinPrice <coin_id> <target_currency> -> Get the price of a coin in a target currency");
     println!("  - CompareCoins <coin1_id> <coin2_id> <target_currency> -> Compare two coins");
+    println!("  - PriceHistory <coin_id> <target_currency> <days> -> Show daily price history");
+    println!("  - Pass --source <provider> to prefer coinpaprika or coingecko (falls back automatically)");
+    println!("  - ExchangeInfo            -> List tradable pairs and their price/quantity scales");
+    println!("  - Pass --max-age <seconds> to control how long cached quotes are reused (default 60)");
     println!("=========================================================\n");
 
     let cli = Cli::parse();
@@ -100,14 +307,24 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             coin_id,
             target_currency,
         } => {
-            get_coin_price(coin_id, target_currency).await?;
+            get_coin_price(coin_id, target_currency, &cli.source, cli.max_age).await?;
         }
         Commands::CompareCoins {
             coin1_id,
             coin2_id,
             target_currency,
         } => {
-            compare_coin_prices(coin1_id, coin2_id, target_currency).await?;
+            compare_coin_prices(coin1_id, coin2_id, target_currency, &cli.source, cli.max_age).await?;
+        }
+        Commands::PriceHistory {
+            coin_id,
+            target_currency,
+            days,
+        } => {
+            get_price_history(coin_id, target_currency, *days).await?;
+        }
+        Commands::ExchangeInfo => {
+            exchange_info().await?;
         }
     }
 
@@ -138,32 +355,48 @@ async fn get_coin_details(coin_id: &str) -> Result<(), Box<dyn std::error::Error
     println!("=======================================\n");
 
     println!(
-        "Name: {}\nSymbol: {}\nDescription: {}\nRank: {}",
-        response.name, response.symbol, response.description, response.rank
+        "Id: {}\nName: {}\nSymbol: {}\nDescription: {}\nRank: {}",
+        response.id, response.name, response.symbol, response.description, response.rank
     );
 
     Ok(())
 }
 
-async fn get_coin_price(coin_id: &str, target_currency: &str) -> Result<(), Box<dyn std::error::Error>> {
-    let url = format!("https://api.coinpaprika.com/v1/tickers/{}", coin_id);
-    let response = reqwest::get(&url).await?.json::<TickerResponse>().await?;
+async fn get_coin_price(
+    coin_id: &str,
+    target_currency: &str,
+    source: &str,
+    max_age: u64,
+) -> Result<(), Box<dyn std::error::Error>> {
+    // Metadata (display name/symbol) is coinpaprika-only; tolerate it being unavailable so a
+    // coinpaprika outage doesn't block the fallback chain from still resolving a price below.
+    let metadata = fetch_ticker_cached(coin_id, max_age).await.ok();
+    let display_name = metadata.as_ref().map(|r| r.name.as_str()).unwrap_or(coin_id);
+    let display_symbol = metadata.as_ref().map(|r| r.symbol.as_str()).unwrap_or("");
 
     let target_currency_upper = target_currency.to_uppercase();
     println!("\n=======================================");
-    println!("       Price for {} ({}) in {}        ", response.name, response.symbol, target_currency_upper);
+    println!("       Price for {} ({}) in {}        ", display_name, display_symbol, target_currency_upper);
     println!("=======================================\n");
 
-    if let Some(quote) = response.quotes.get(&target_currency_upper) {
-        println!(
-            "1 {} ({}) = {} {}",
-            response.name, response.symbol, quote.price, target_currency_upper
-        );
-    } else {
-        println!(
-            "Could not find price information for {} in {}",
-            response.name, target_currency_upper
-        );
+    let providers = build_providers(source, max_age);
+    match approx_price_for_pair(coin_id, target_currency, &providers, max_age).await {
+        Ok((price, approximated)) => {
+            println!(
+                "1 {} ({}) = {} {}{}",
+                display_name,
+                display_symbol,
+                price,
+                target_currency_upper,
+                if approximated { " (approximated via USD bridge)" } else { "" }
+            );
+        }
+        Err(_) => {
+            println!(
+                "Could not find price information for {} in {}",
+                display_name, target_currency_upper
+            );
+        }
     }
 
     Ok(())
@@ -173,16 +406,31 @@ async fn compare_coin_prices(
     coin1_id: &str,
     coin2_id: &str,
     target_currency: &str,
+    source: &str,
+    max_age: u64,
 ) -> Result<(), Box<dyn std::error::Error>> {
-    let coin1_price = get_coin_price_value(coin1_id, target_currency).await?;
-    let coin2_price = get_coin_price_value(coin2_id, target_currency).await?;
+    let providers = build_providers(source, max_age);
+    let (coin1_price, coin1_approx) = approx_price_for_pair(coin1_id, target_currency, &providers, max_age).await?;
+    let (coin2_price, coin2_approx) = approx_price_for_pair(coin2_id, target_currency, &providers, max_age).await?;
 
     println!("\n=======================================");
     println!("  Comparing {} and {} in {} Currency  ", coin1_id, coin2_id, target_currency.to_uppercase());
     println!("=======================================\n");
 
-    println!("{} price: {} {}", coin1_id, coin1_price, target_currency.to_uppercase());
-    println!("{} price: {} {}", coin2_id, coin2_price, target_currency.to_uppercase());
+    println!(
+        "{} price: {} {}{}",
+        coin1_id,
+        coin1_price,
+        target_currency.to_uppercase(),
+        if coin1_approx { " (approximated via USD bridge)" } else { "" }
+    );
+    println!(
+        "{} price: {} {}{}",
+        coin2_id,
+        coin2_price,
+        target_currency.to_uppercase(),
+        if coin2_approx { " (approximated via USD bridge)" } else { "" }
+    );
 
     if coin1_price > coin2_price {
         println!(
@@ -204,9 +452,81 @@ async fn compare_coin_prices(
     Ok(())
 }
 
-async fn get_coin_price_value(coin_id: &str, target_currency: &str) -> Result<f64, Box<dyn std::error::Error>> {
-    let url = format!("https://api.coinpaprika.com/v1/tickers/{}", coin_id);
-    let response = reqwest::get(&url).await?.json::<TickerResponse>().await?;
+async fn get_price_history(
+    coin_id: &str,
+    target_currency: &str,
+    days: u32,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let requested_days = days;
+    let days = if days > FREE_TIER_MAX_HISTORY_DAYS {
+        println!(
+            "Note: the free API tier only retains {} days of history; showing the last {} days instead of the requested {}.",
+            FREE_TIER_MAX_HISTORY_DAYS, FREE_TIER_MAX_HISTORY_DAYS, requested_days
+        );
+        FREE_TIER_MAX_HISTORY_DAYS
+    } else {
+        days
+    };
+
+    let start = (Utc::now() - Duration::days(days as i64))
+        .format("%Y-%m-%d")
+        .to_string();
+    let url = format!(
+        "https://api.coinpaprika.com/v1/coins/{}/ohlcv/historical?start={}",
+        coin_id, start
+    );
+    let response = reqwest::get(&url)
+        .await?
+        .json::<Vec<HistoricalQuote>>()
+        .await?;
+
+    let target_currency_upper = target_currency.to_uppercase();
+    if target_currency_upper != "USD" {
+        println!(
+            "Note: coinpaprika's free OHLCV endpoint only reports prices in USD; ignoring requested currency {}.",
+            target_currency_upper
+        );
+    }
+
+    println!("\n=======================================");
+    println!("   {}-day Price History for {} in USD   ", days, coin_id);
+    println!("=======================================\n");
+
+    for quote in &response {
+        println!(
+            "{}: open {} high {} low {} close {} volume {}",
+            quote.timestamp, quote.open, quote.high, quote.low, quote.close, quote.volume
+        );
+    }
+
+    Ok(())
+}
+
+async fn exchange_info() -> Result<(), Box<dyn std::error::Error>> {
+    let url = "https://api.binance.com/api/v3/exchangeInfo";
+    let response = reqwest::get(url).await?.json::<ExchangeInfo>().await?;
+
+    println!("\n=======================================");
+    println!("        Tradable Pairs & Scales         ");
+    println!("=======================================\n");
+    println!("Server time: {}\n", response.server_time);
+
+    for pair in &response.symbols {
+        println!(
+            "{} ({}/{}): price scale {} decimals, quantity scale {} decimals",
+            pair.symbol, pair.base_asset, pair.quote_asset, pair.price_scale, pair.quantity_scale
+        );
+    }
+
+    Ok(())
+}
+
+async fn get_coin_price_value(
+    coin_id: &str,
+    target_currency: &str,
+    max_age: u64,
+) -> Result<Decimal, Box<dyn std::error::Error>> {
+    let response = fetch_ticker_cached(coin_id, max_age).await?;
 
     let target_currency_upper = target_currency.to_uppercase();
     if let Some(quote) = response.quotes.get(&target_currency_upper) {
@@ -219,3 +539,199 @@ async fn get_coin_price_value(coin_id: &str, target_currency: &str) -> Result<f6
         .into())
     }
 }
+
+/// Fetches `id`'s USD quote from its ticker, used as the bridge currency for triangulation.
+async fn get_usd_price(id: &str, max_age: u64) -> Result<Decimal, Box<dyn std::error::Error>> {
+    let response = fetch_ticker_cached(id, max_age).await?;
+
+    response
+        .quotes
+        .get("USD")
+        .map(|quote| quote.price)
+        .ok_or_else(|| format!("Could not find a USD quote for {}", id).into())
+}
+
+/// Short currency codes that don't match their coinpaprika coin id, mapped directly to avoid a
+/// coin-list lookup for the common bridge currencies.
+const KNOWN_BRIDGE_COIN_IDS: &[(&str, &str)] = &[
+    ("BTC", "btc-bitcoin"),
+    ("ETH", "eth-ethereum"),
+    ("USDT", "usdt-tether"),
+    ("DOGE", "doge-dogecoin"),
+    ("BNB", "bnb-binance-coin"),
+];
+
+/// Resolves a short currency code (e.g. `eth`, `doge`) to the coinpaprika coin id needed to fetch
+/// its USD quote, falling back to a symbol search over the full coin list.
+async fn resolve_bridge_coin_id(currency: &str) -> Result<String, Box<dyn std::error::Error>> {
+    let currency_upper = currency.to_uppercase();
+    if let Some((_, id)) = KNOWN_BRIDGE_COIN_IDS
+        .iter()
+        .find(|(symbol, _)| *symbol == currency_upper)
+    {
+        return Ok((*id).to_string());
+    }
+
+    let coins = reqwest::get("https://api.coinpaprika.com/v1/coins")
+        .await?
+        .json::<Vec<Coin>>()
+        .await?;
+
+    coins
+        .into_iter()
+        .find(|coin| coin.symbol.eq_ignore_ascii_case(&currency_upper))
+        .map(|coin| coin.id)
+        .ok_or_else(|| format!("Could not resolve bridge currency {} to a coin id", currency).into())
+}
+
+/// Resolves `coin_id`'s price in `target_currency` using the given provider chain, approximating
+/// via a USD bridge when none of the providers have a direct quote in that currency (e.g.
+/// `target_currency` is itself a coin).
+///
+/// Returns `(price, approximated)`, where `approximated` is `true` when the price was derived
+/// from the cross-rate rather than read directly off a provider's native quote.
+async fn approx_price_for_pair(
+    coin_id: &str,
+    target_currency: &str,
+    providers: &[Box<dyn QuotesProvider>],
+    max_age: u64,
+) -> Result<(Decimal, bool), Box<dyn std::error::Error>> {
+    if let Ok(price) = get_quote_with_fallback(providers, coin_id, target_currency).await {
+        return Ok((price, false));
+    }
+
+    let price_in_usd = get_usd_price(coin_id, max_age).await?;
+    let target_coin_id = resolve_bridge_coin_id(target_currency).await?;
+    let target_in_usd = get_usd_price(&target_coin_id, max_age).await?;
+
+    if target_in_usd == Decimal::ZERO {
+        return Err(format!("USD price for bridge currency {} was zero", target_currency).into());
+    }
+
+    Ok((price_in_usd / target_in_usd, true))
+}
+
+/// A source of live coin/currency quotes. Implementations wrap a specific market-data API so the
+/// CLI can fall through to an alternative source if one is down.
+#[async_trait::async_trait]
+trait QuotesProvider {
+    /// Short identifier users pass via `--source` (e.g. "coinpaprika").
+    fn name(&self) -> &'static str;
+
+    async fn get_quote(&self, coin_id: &str, currency: &str) -> Result<Decimal, Box<dyn std::error::Error>>;
+}
+
+struct CoinpaprikaProvider {
+    max_age: u64,
+}
+
+#[async_trait::async_trait]
+impl QuotesProvider for CoinpaprikaProvider {
+    fn name(&self) -> &'static str {
+        "coinpaprika"
+    }
+
+    async fn get_quote(&self, coin_id: &str, currency: &str) -> Result<Decimal, Box<dyn std::error::Error>> {
+        get_coin_price_value(coin_id, currency, self.max_age).await
+    }
+}
+
+struct CoinGeckoProvider;
+
+#[derive(Deserialize, Debug)]
+struct CoinGeckoCoin {
+    id: String,
+    symbol: String,
+}
+
+/// coinpaprika symbols mapped directly to their CoinGecko id, for the common coins whose
+/// CoinGecko id doesn't share coinpaprika's slug (e.g. `bnb-binance-coin` -> `binancecoin`, not
+/// `binance-coin`). Kept small to avoid a coin-list lookup for everyday queries.
+const KNOWN_COINGECKO_IDS: &[(&str, &str)] = &[
+    ("btc", "bitcoin"),
+    ("eth", "ethereum"),
+    ("usdt", "tether"),
+    ("doge", "dogecoin"),
+    ("bnb", "binancecoin"),
+];
+
+/// Resolves a coinpaprika-style coin id (`{symbol}-{slug}`) to the CoinGecko id needed to query
+/// its API, which does not reliably share coinpaprika's slug. Falls back to a symbol search over
+/// CoinGecko's full coin list for ids outside the common map.
+async fn to_coingecko_id(coin_id: &str) -> Result<String, Box<dyn std::error::Error>> {
+    let symbol = coin_id.split('-').next().unwrap_or(coin_id);
+    if let Some((_, id)) = KNOWN_COINGECKO_IDS.iter().find(|(s, _)| *s == symbol) {
+        return Ok((*id).to_string());
+    }
+
+    let coins = reqwest::get("https://api.coingecko.com/api/v3/coins/list")
+        .await?
+        .json::<Vec<CoinGeckoCoin>>()
+        .await?;
+
+    coins
+        .into_iter()
+        .find(|coin| coin.symbol.eq_ignore_ascii_case(symbol))
+        .map(|coin| coin.id)
+        .ok_or_else(|| format!("Could not resolve {} to a coingecko id", coin_id).into())
+}
+
+#[async_trait::async_trait]
+impl QuotesProvider for CoinGeckoProvider {
+    fn name(&self) -> &'static str {
+        "coingecko"
+    }
+
+    async fn get_quote(&self, coin_id: &str, currency: &str) -> Result<Decimal, Box<dyn std::error::Error>> {
+        let coingecko_id = to_coingecko_id(coin_id).await?;
+        let currency_lower = currency.to_lowercase();
+        let url = format!(
+            "https://api.coingecko.com/api/v3/simple/price?ids={}&vs_currencies={}",
+            coingecko_id, currency_lower
+        );
+        let response = reqwest::get(&url)
+            .await?
+            .json::<HashMap<String, HashMap<String, PreciseDecimal>>>()
+            .await?;
+
+        response
+            .get(&coingecko_id)
+            .and_then(|quotes| quotes.get(&currency_lower))
+            .map(|d| d.0)
+            .ok_or_else(|| format!("coingecko has no {} quote for {}", currency_lower, coingecko_id).into())
+    }
+}
+
+/// Wraps `Decimal` so values nested in a generic container (where a field-level
+/// `#[serde(with = "rust_decimal::serde::arbitrary_precision")]` isn't available) still
+/// deserialize without passing through a lossy f64 first.
+#[derive(Deserialize, Serialize, Debug, Clone, Copy)]
+struct PreciseDecimal(#[serde(with = "rust_decimal::serde::arbitrary_precision")] Decimal);
+
+/// Builds the provider chain for a run, trying `preferred` first and falling back to the rest of
+/// the configured providers in a fixed order.
+fn build_providers(preferred: &str, max_age: u64) -> Vec<Box<dyn QuotesProvider>> {
+    let mut providers: Vec<Box<dyn QuotesProvider>> =
+        vec![Box::new(CoinpaprikaProvider { max_age }), Box::new(CoinGeckoProvider)];
+    if let Some(pos) = providers.iter().position(|p| p.name() == preferred) {
+        let preferred = providers.remove(pos);
+        providers.insert(0, preferred);
+    }
+    providers
+}
+
+/// Tries each configured provider in order, falling through to the next on any failure.
+async fn get_quote_with_fallback(
+    providers: &[Box<dyn QuotesProvider>],
+    coin_id: &str,
+    currency: &str,
+) -> Result<Decimal, Box<dyn std::error::Error>> {
+    let mut last_err = None;
+    for provider in providers {
+        match provider.get_quote(coin_id, currency).await {
+            Ok(price) => return Ok(price),
+            Err(err) => last_err = Some(err),
+        }
+    }
+    Err(last_err.unwrap_or_else(|| "no quotes providers configured".into()))
+}